@@ -0,0 +1,58 @@
+use tree_sitter::{InputEdit, Point};
+
+/// Computes the tree-sitter `InputEdit` describing how `new_text` differs from
+/// `old_text`, using their common prefix/suffix as the unaffected regions.
+///
+/// This is for callers that only have full before/after text (e.g. a frontend
+/// that resends the whole buffer on every keystroke) rather than a structured
+/// edit event — it's the minimum information `SyntaxEngine::edit` needs to let
+/// tree-sitter reuse unchanged subtrees instead of reparsing from scratch.
+pub fn compute_edit(old_text: &str, new_text: &str) -> InputEdit {
+    let old_bytes = old_text.as_bytes();
+    let new_bytes = new_text.as_bytes();
+    let max_common = old_bytes.len().min(new_bytes.len());
+
+    let mut prefix = 0;
+    while prefix < max_common && old_bytes[prefix] == new_bytes[prefix] {
+        prefix += 1;
+    }
+    while prefix > 0 && !old_text.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let mut suffix = 0;
+    while suffix < max_common - prefix
+        && old_bytes[old_bytes.len() - 1 - suffix] == new_bytes[new_bytes.len() - 1 - suffix]
+    {
+        suffix += 1;
+    }
+    while suffix > 0
+        && (!old_text.is_char_boundary(old_bytes.len() - suffix)
+            || !new_text.is_char_boundary(new_bytes.len() - suffix))
+    {
+        suffix -= 1;
+    }
+
+    let start_byte = prefix;
+    let old_end_byte = old_bytes.len() - suffix;
+    let new_end_byte = new_bytes.len() - suffix;
+
+    InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at_byte(old_text, start_byte),
+        old_end_position: point_at_byte(old_text, old_end_byte),
+        new_end_position: point_at_byte(new_text, new_end_byte),
+    }
+}
+
+fn point_at_byte(text: &str, byte_idx: usize) -> Point {
+    let prefix = &text.as_bytes()[..byte_idx];
+    let row = prefix.iter().filter(|&&b| b == b'\n').count();
+    let column = match prefix.iter().rposition(|&b| b == b'\n') {
+        Some(last_newline) => byte_idx - last_newline - 1,
+        None => byte_idx,
+    };
+    Point::new(row, column)
+}
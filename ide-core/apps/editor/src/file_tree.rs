@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::syntax::grammar_registry::GrammarRegistry;
+use crate::syntax::SupportedLanguage;
+
+/// Directory/file names skipped unconditionally, on top of whatever the root's
+/// `.gitignore` adds.
+const DEFAULT_IGNORED: &[&str] = &[".git", "target", "node_modules"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FileNode {
+    Directory {
+        name: String,
+        path: PathBuf,
+        children: Vec<FileNode>,
+    },
+    File {
+        name: String,
+        path: PathBuf,
+        /// Display name of the detected language, from the built-in enum or,
+        /// failing that, a registry-loaded grammar. `None` if neither knows
+        /// the file's extension.
+        language: Option<String>,
+    },
+}
+
+/// Detects a file's language by extension, built-in enum first and falling
+/// back to `registry` so runtime-loaded grammars get frontend icons too.
+fn detect_language(path: &Path, registry: Option<&GrammarRegistry>) -> Option<String> {
+    let ext = path.extension()?.to_str()?;
+    if let Some(language) = SupportedLanguage::from_extension(ext) {
+        return Some(language.to_string());
+    }
+    registry
+        .and_then(|registry| registry.grammar_for_extension(ext))
+        .map(|grammar| grammar.name.clone())
+}
+
+/// A best-effort `.gitignore`-style matcher: supports exact names and a single
+/// leading or trailing `*` wildcard. Not a full gitignore implementation (no
+/// nested-directory patterns, negation, etc.) — just enough to keep build
+/// output and dependency directories out of a project tree sidebar.
+struct IgnoreRules {
+    patterns: Vec<String>,
+}
+
+impl IgnoreRules {
+    fn from_gitignore(root: &Path) -> Self {
+        let mut patterns: Vec<String> = DEFAULT_IGNORED.iter().map(|s| s.to_string()).collect();
+
+        if let Ok(contents) = fs::read_to_string(root.join(".gitignore")) {
+            for line in contents.lines() {
+                let line = line.trim();
+                if !line.is_empty() && !line.starts_with('#') {
+                    patterns.push(line.trim_end_matches('/').to_string());
+                }
+            }
+        }
+
+        Self { patterns }
+    }
+
+    fn is_ignored(&self, name: &str) -> bool {
+        self.patterns.iter().any(|pattern| Self::matches(pattern, name))
+    }
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        if let Some(suffix) = pattern.strip_prefix('*') {
+            name.ends_with(suffix)
+        } else if let Some(prefix) = pattern.strip_suffix('*') {
+            name.starts_with(prefix)
+        } else {
+            pattern == name
+        }
+    }
+}
+
+/// Recursively walks `root`, skipping `.git`/`target`/`node_modules` and
+/// anything matched by the root's `.gitignore`, and returns a structured tree
+/// (directories with children) rather than a flat file list — the backbone for
+/// a project tree sidebar. Files are tagged with their detected language,
+/// falling back to `registry` (if given) for extensions the built-in enum
+/// doesn't know, so the frontend can pick icons.
+pub fn walk_project(root: &Path, registry: Option<&GrammarRegistry>) -> Result<FileNode> {
+    let ignore = IgnoreRules::from_gitignore(root);
+    walk_dir(root, &ignore, registry)
+}
+
+fn walk_dir(dir: &Path, ignore: &IgnoreRules, registry: Option<&GrammarRegistry>) -> Result<FileNode> {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.display().to_string());
+
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut children = Vec::new();
+    for entry in entries {
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        if ignore.is_ignored(&entry_name) {
+            continue;
+        }
+
+        let path = entry.path();
+        if path.is_dir() {
+            // A single unreadable subdirectory (permissions, a broken symlink, a
+            // race with something deleting it) shouldn't blank out the rest of
+            // the tree — skip it and keep walking siblings.
+            match walk_dir(&path, ignore, registry) {
+                Ok(child) => children.push(child),
+                Err(err) => eprintln!("Skipping {}: {err:#}", path.display()),
+            }
+        } else {
+            let language = detect_language(&path, registry);
+            children.push(FileNode::File {
+                name: entry_name,
+                path,
+                language,
+            });
+        }
+    }
+
+    Ok(FileNode::Directory {
+        name,
+        path: dir.to_path_buf(),
+        children,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_exact() {
+        assert!(IgnoreRules::matches("target", "target"));
+        assert!(!IgnoreRules::matches("target", "targets"));
+    }
+
+    #[test]
+    fn test_matches_leading_wildcard() {
+        assert!(IgnoreRules::matches("*.lock", "Cargo.lock"));
+        assert!(!IgnoreRules::matches("*.lock", "Cargo.toml"));
+    }
+
+    #[test]
+    fn test_matches_trailing_wildcard() {
+        assert!(IgnoreRules::matches("build*", "build-output"));
+        assert!(!IgnoreRules::matches("build*", "output-build"));
+    }
+
+    #[test]
+    fn test_is_ignored_includes_defaults_and_gitignore_patterns() {
+        let ignore = IgnoreRules {
+            patterns: vec![".git".to_string(), "*.tmp".to_string()],
+        };
+        assert!(ignore.is_ignored(".git"));
+        assert!(ignore.is_ignored("scratch.tmp"));
+        assert!(!ignore.is_ignored("main.rs"));
+    }
+}
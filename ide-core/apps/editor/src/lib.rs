@@ -0,0 +1,5 @@
+pub mod diff;
+pub mod file_tree;
+pub mod stats;
+pub mod syntax;
+pub mod text_buffer;
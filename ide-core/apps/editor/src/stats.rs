@@ -0,0 +1,261 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::file_tree::{self, FileNode};
+use crate::syntax::SupportedLanguage;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LanguageStats {
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+    pub files: usize,
+}
+
+/// Per-language comment token table: single-line markers (`//`, `#`, ...) and
+/// block delimiter pairs (`/* */`, `""" """`, ...).
+struct CommentTokens {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+}
+
+fn tokens_for(language: &SupportedLanguage) -> CommentTokens {
+    match language {
+        SupportedLanguage::CPP
+        | SupportedLanguage::CSharp
+        | SupportedLanguage::JavaScript
+        | SupportedLanguage::Rust
+        | SupportedLanguage::TypeScript
+        | SupportedLanguage::TSX => CommentTokens {
+            line: &["//"],
+            block: &[("/*", "*/")],
+        },
+        SupportedLanguage::Python => CommentTokens {
+            line: &["#"],
+            block: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+        },
+    }
+}
+
+#[derive(PartialEq)]
+enum LineKind {
+    Blank,
+    Comment,
+    Code,
+}
+
+enum Delimiter {
+    Line,
+    Block(&'static str, &'static str),
+}
+
+/// Classifies one line and updates `block_stack`, the stack of (open, close)
+/// token pairs for currently-open block comments (its length is the nesting
+/// depth). A line that starts inside an open block (`block_stack` non-empty
+/// on entry) is always a comment line, even if its block closes partway
+/// through — matching the rest of the scanner, which walks left to right
+/// pushing another pair on every nested open of the *same* delimiter and
+/// popping on a matching close, so e.g. nested Rust `/* /* */ */` block
+/// comments (and a line that both opens and closes a block comment) are
+/// still counted correctly. Delimiters whose open and close tokens are
+/// identical (Python's `"""`/`'''`) never nest, since an "open" found before
+/// the next "close" is, by definition, that same close token.
+fn classify_line(
+    line: &str,
+    tokens: &CommentTokens,
+    block_stack: &mut Vec<(&'static str, &'static str)>,
+) -> LineKind {
+    if line.trim().is_empty() && block_stack.is_empty() {
+        return LineKind::Blank;
+    }
+
+    let started_in_comment = !block_stack.is_empty();
+    let mut has_code = false;
+    let mut rest = line;
+
+    loop {
+        if let Some(&(open, close)) = block_stack.last() {
+            let next_open = rest.find(open);
+            let next_close = rest.find(close);
+            match (next_open, next_close) {
+                (Some(open_idx), Some(close_idx)) if open_idx < close_idx => {
+                    block_stack.push((open, close));
+                    rest = &rest[open_idx + open.len()..];
+                }
+                (_, Some(close_idx)) => {
+                    block_stack.pop();
+                    rest = &rest[close_idx + close.len()..];
+                }
+                (Some(open_idx), None) => {
+                    block_stack.push((open, close));
+                    rest = &rest[open_idx + open.len()..];
+                }
+                (None, None) => break,
+            }
+        } else {
+            let mut earliest: Option<(usize, Delimiter)> = None;
+            for &marker in tokens.line {
+                if let Some(idx) = rest.find(marker) {
+                    if earliest.as_ref().map_or(true, |(e, _)| idx < *e) {
+                        earliest = Some((idx, Delimiter::Line));
+                    }
+                }
+            }
+            for &(open, close) in tokens.block {
+                if let Some(idx) = rest.find(open) {
+                    if earliest.as_ref().map_or(true, |(e, _)| idx < *e) {
+                        earliest = Some((idx, Delimiter::Block(open, close)));
+                    }
+                }
+            }
+
+            match earliest {
+                None => {
+                    has_code |= !rest.trim().is_empty();
+                    break;
+                }
+                Some((idx, Delimiter::Line)) => {
+                    has_code |= !rest[..idx].trim().is_empty();
+                    break;
+                }
+                Some((idx, Delimiter::Block(open, close))) => {
+                    has_code |= !rest[..idx].trim().is_empty();
+                    block_stack.push((open, close));
+                    rest = &rest[idx + open.len()..];
+                }
+            }
+        }
+
+        if rest.is_empty() {
+            break;
+        }
+    }
+
+    if started_in_comment || !has_code {
+        LineKind::Comment
+    } else {
+        LineKind::Code
+    }
+}
+
+/// Scans `source` line by line, tracking block-comment nesting depth across
+/// lines, and returns its code/comment/blank line counts. `files` is left at 0;
+/// callers aggregating multiple files bump it themselves.
+pub fn scan_source(source: &str, language: &SupportedLanguage) -> LanguageStats {
+    let tokens = tokens_for(language);
+    let mut block_stack = Vec::new();
+    let mut stats = LanguageStats::default();
+
+    for line in source.lines() {
+        match classify_line(line, &tokens, &mut block_stack) {
+            LineKind::Blank => stats.blanks += 1,
+            LineKind::Comment => stats.comments += 1,
+            LineKind::Code => stats.code += 1,
+        }
+    }
+
+    stats
+}
+
+/// Recursively scans `root` (skipping whatever [`file_tree::walk_project`]
+/// ignores) and reports code/comment/blank line counts per language, for a
+/// project-overview panel.
+///
+/// Stats are only meaningful for the compile-time `SupportedLanguage` table
+/// (it's what [`tokens_for`] knows how to scan), so this re-derives the
+/// language from each file's extension directly rather than trusting
+/// `FileNode`'s display-name `language` field, which may point at a
+/// registry-loaded grammar `tokens_for` has no entry for.
+pub fn collect_stats(root: &Path) -> Result<HashMap<String, LanguageStats>> {
+    let tree = file_tree::walk_project(root, None)?;
+    let mut totals = HashMap::new();
+    accumulate(&tree, &mut totals);
+    Ok(totals)
+}
+
+fn accumulate(node: &FileNode, totals: &mut HashMap<String, LanguageStats>) {
+    match node {
+        FileNode::Directory { children, .. } => {
+            for child in children {
+                accumulate(child, totals);
+            }
+        }
+        FileNode::File { path, .. } => {
+            let Some(language) = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(SupportedLanguage::from_extension)
+            else {
+                return;
+            };
+            let Ok(contents) = fs::read_to_string(path) else {
+                return;
+            };
+            let file_stats = scan_source(&contents, &language);
+            let entry = totals.entry(language.to_string()).or_default();
+            entry.code += file_stats.code;
+            entry.comments += file_stats.comments;
+            entry.blanks += file_stats.blanks;
+            entry.files += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_source_rust_line_comments_and_blanks() {
+        let source = "fn main() {\n\n    // a comment\n    let x = 1;\n}\n";
+        let stats = scan_source(source, &SupportedLanguage::Rust);
+        assert_eq!(stats.blanks, 1);
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 3);
+    }
+
+    #[test]
+    fn test_scan_source_rust_block_comment_spanning_lines() {
+        let source = "/*\n * still a comment\n */\nlet x = 1;\n";
+        let stats = scan_source(source, &SupportedLanguage::Rust);
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn test_scan_source_rust_nested_block_comment_single_line() {
+        // Rust (unlike C) nests `/* */`: the first `*/` closes only the inner
+        // comment, not the outer one, so the whole line is still a comment.
+        let source = "/* outer /* inner */ still comment here */\nlet x = 1;\n";
+        let stats = scan_source(source, &SupportedLanguage::Rust);
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn test_scan_source_rust_nested_block_comment_across_lines() {
+        let source = "/* outer\n/* inner */\nstill outer */\nlet x = 1;\n";
+        let stats = scan_source(source, &SupportedLanguage::Rust);
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn test_scan_source_line_with_both_code_and_comment() {
+        let source = "let x = 1; // trailing comment\n";
+        let stats = scan_source(source, &SupportedLanguage::Rust);
+        assert_eq!(stats.code, 1);
+        assert_eq!(stats.comments, 0);
+    }
+
+    #[test]
+    fn test_scan_source_python_triple_quoted_block() {
+        let source = "\"\"\"\nmodule docstring\n\"\"\"\nx = 1\n";
+        let stats = scan_source(source, &SupportedLanguage::Python);
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.code, 1);
+    }
+}
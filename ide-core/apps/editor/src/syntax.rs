@@ -1,7 +1,10 @@
-use tree_sitter::{Node, Parser, Tree};
+use tree_sitter::{Parser, Query, QueryCursor, Tree};
 use std::fmt;
 use serde::Serialize;
 
+pub mod grammar_registry;
+pub mod outline;
+
 // Language modules
 use tree_sitter_cpp as tscpp;
 use tree_sitter_c_sharp as tscs;
@@ -35,7 +38,7 @@ pub struct HighlightSpan {
     pub highlight_type: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum SupportedLanguage {
     CPP,
     CSharp,
@@ -59,6 +62,21 @@ impl SupportedLanguage {
         }
     }
 
+    /// Returns the language's tree-sitter highlight query (the `.scm` source the
+    /// grammar crate ships as a `HIGHLIGHT_QUERY` constant), used to drive
+    /// capture-based highlighting instead of walking raw node kinds.
+    pub fn highlight_query(&self) -> &'static str {
+        match self {
+            SupportedLanguage::CPP => tscpp::HIGHLIGHT_QUERY,
+            SupportedLanguage::CSharp => tscs::HIGHLIGHT_QUERY,
+            SupportedLanguage::JavaScript => tsjs::HIGHLIGHT_QUERY,
+            SupportedLanguage::Python => tspy::HIGHLIGHT_QUERY,
+            SupportedLanguage::Rust => tsrs::HIGHLIGHT_QUERY,
+            SupportedLanguage::TypeScript => tree_sitter_typescript::HIGHLIGHT_QUERY,
+            SupportedLanguage::TSX => tree_sitter_typescript::HIGHLIGHT_QUERY,
+        }
+    }
+
     pub fn from_extension(ext: &str) -> Option<Self> {
         match ext {
             "cpp" | "cxx" | "cc" => Some(Self::CPP),
@@ -93,57 +111,181 @@ pub enum SyntaxError {
     ParseFailed,
 }
 
+/// The language backing a [`SyntaxEngine`]: either one of the compile-time
+/// `SupportedLanguage` variants, or a grammar resolved at runtime through a
+/// [`grammar_registry::GrammarRegistry`]. Runtime-loaded grammars don't carry a
+/// built-in highlight or outline query, so engines built from them degrade
+/// gracefully to no highlights/outline rather than failing.
+#[derive(Debug, Clone)]
+pub enum EngineLanguage {
+    BuiltIn(SupportedLanguage),
+    Loaded(String),
+}
+
 pub struct SyntaxEngine {
     parser: Parser,
-    language: SupportedLanguage,
+    language: EngineLanguage,
+    tree: Option<Tree>,
+    highlight_query: Option<Query>,
 }
 
 impl SyntaxEngine {
     pub fn new(language: SupportedLanguage) -> Self {
+        let ts_language = language.tree_sitter_language();
         let mut parser = Parser::new();
         parser
-            .set_language(language.tree_sitter_language())
+            .set_language(ts_language)
             .expect("Failed to set Tree-sitter language");
-        Self { parser, language }
+        // A mismatch between the bundled HIGHLIGHT_QUERY and the linked grammar
+        // version must not be fatal: degrade to no highlights for this language
+        // (same as the runtime-loaded-grammar path) instead of panicking on
+        // every file-open.
+        let highlight_query = match Query::new(ts_language, language.highlight_query()) {
+            Ok(query) => Some(query),
+            Err(err) => {
+                eprintln!("Failed to compile highlight query for {language}: {err}");
+                None
+            }
+        };
+        Self {
+            parser,
+            language: EngineLanguage::BuiltIn(language),
+            tree: None,
+            highlight_query,
+        }
     }
 
+    /// Builds an engine around a grammar resolved at runtime by a
+    /// [`grammar_registry::GrammarRegistry`] instead of the built-in enum.
+    /// `name` is the grammar's name (for display/debugging), and `language` is
+    /// the `Language` handle the registry obtained from the loaded library —
+    /// the registry is responsible for keeping the backing `Library` alive.
+    pub fn from_loaded(name: impl Into<String>, language: tree_sitter::Language) -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(language)
+            .expect("Failed to set Tree-sitter language");
+        Self {
+            parser,
+            language: EngineLanguage::Loaded(name.into()),
+            tree: None,
+            highlight_query: None,
+        }
+    }
+
+    /// Parses `source`, reusing the previously stored tree (if any) so tree-sitter can
+    /// reuse unchanged subtrees. Callers that mutate the underlying buffer should feed
+    /// each change through [`SyntaxEngine::edit`] before calling this again, otherwise
+    /// the reused tree and the new source will disagree and the returned tree can be
+    /// wrong; this method does not detect a stale tree and does not reparse from
+    /// scratch in that case; only [`SyntaxEngine::reset`] forces a full reparse.
     pub fn parse(&mut self, source: &str) -> Result<Tree, SyntaxError> {
-        self.parser
-            .parse(source, None)
-            .ok_or(SyntaxError::ParseFailed)
+        let old_tree = self.tree.as_ref();
+        let tree = self
+            .parser
+            .parse(source, old_tree)
+            .ok_or(SyntaxError::ParseFailed)?;
+        self.tree = Some(tree.clone());
+        Ok(tree)
     }
 
-    pub fn current_language(&self) -> &SupportedLanguage {
+    /// Applies a tree-sitter edit to the stored tree so the next `parse` call can reuse
+    /// unaffected subtrees instead of reparsing the whole file. No-op if no tree has
+    /// been parsed yet.
+    pub fn edit(&mut self, edit: &tree_sitter::InputEdit) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.edit(edit);
+        }
+    }
+
+    /// Drops the stored tree, forcing the next `parse` to do a full reparse. Use this
+    /// when the language changes or edits can't be expressed as a simple `InputEdit`.
+    pub fn reset(&mut self) {
+        self.tree = None;
+    }
+
+    pub fn current_language(&self) -> &EngineLanguage {
         &self.language
     }
 
     pub fn extract_highlights(&mut self, source: &str) -> Vec<HighlightSpan> {
-        let tree = match self.parser.parse(source, None) {
-            Some(t) => t,
-            None => return vec![],
+        let tree = match self.parse(source) {
+            Ok(t) => t,
+            Err(_) => return vec![],
         };
-        Self::extract_highlights_from_tree(&tree)
+        match &self.highlight_query {
+            Some(query) => Self::extract_highlights_from_tree(&tree, query, source),
+            None => Vec::new(),
+        }
     }
 
-    pub fn extract_highlights_from_tree(tree: &Tree) -> Vec<HighlightSpan> {
-        let mut highlights = Vec::new();
-        let root_node = tree.root_node();
+    /// Parses `source` and extracts its document outline (functions, methods,
+    /// classes/structs/enums, impl blocks, ...) as a nested symbol tree. Returns
+    /// an empty outline for runtime-loaded grammars, which don't carry one.
+    pub fn extract_outline(&mut self, source: &str) -> Vec<outline::OutlineItem> {
+        let EngineLanguage::BuiltIn(language) = &self.language else {
+            return Vec::new();
+        };
+        let language = language.clone();
+        let tree = match self.parse(source) {
+            Ok(t) => t,
+            Err(_) => return Vec::new(),
+        };
+        outline::extract_outline(&tree, source, &language)
+    }
 
-        fn recurse(node: Node, highlights: &mut Vec<HighlightSpan>) {
-            if node.is_named() {
-                highlights.push(HighlightSpan {
-                    range: node.range().into(),
-                    highlight_type: node.kind().into(),
-                });
-            }
+    /// Returns the distinct capture names this language's highlight query can
+    /// produce (e.g. `keyword`, `function`, `string`), in query-declaration order,
+    /// so the frontend can map them to theme colors. Empty for runtime-loaded
+    /// grammars, which don't carry a highlight query.
+    pub fn capture_names(&self) -> Vec<String> {
+        match &self.highlight_query {
+            Some(query) => query
+                .capture_names()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Runs `query` over `tree`, emitting one `HighlightSpan` per capture. When
+    /// multiple captures land on the exact same range, the later capture in match
+    /// order wins (the highlight-query convention: more specific patterns are
+    /// written after their more general counterparts). Spans are returned ordered
+    /// from outermost to innermost so a renderer painting them in order naturally
+    /// lets the more specific/innermost capture show on top.
+    pub fn extract_highlights_from_tree(tree: &Tree, query: &Query, source: &str) -> Vec<HighlightSpan> {
+        let mut cursor = QueryCursor::new();
+        let capture_names = query.capture_names();
+
+        // Dedupe captures that land on the exact same byte range, keeping the last
+        // one seen (later capture/pattern wins by highlight-query convention).
+        let mut by_range: std::collections::HashMap<(usize, usize), HighlightSpan> =
+            std::collections::HashMap::new();
 
-            let mut child_cursor = node.walk();
-            for child in node.children(&mut child_cursor) {
-                recurse(child, highlights);
+        for m in cursor.matches(query, tree.root_node(), source.as_bytes()) {
+            for capture in m.captures {
+                let node = capture.node;
+                let key = (node.start_byte(), node.end_byte());
+                by_range.insert(
+                    key,
+                    HighlightSpan {
+                        range: node.range().into(),
+                        highlight_type: capture_names[capture.index as usize].clone(),
+                    },
+                );
             }
         }
 
-        recurse(root_node, &mut highlights);
-        highlights
+        let mut spans: Vec<((usize, usize), HighlightSpan)> = by_range.into_iter().collect();
+        // Order outermost to innermost: by start byte ascending, then by end byte
+        // descending so a wider (less specific) span comes before the narrower
+        // (more specific) one nested inside it. A renderer painting spans in order
+        // then naturally lets the innermost capture show on top.
+        spans.sort_by(|(a_range, _), (b_range, _)| {
+            a_range.0.cmp(&b_range.0).then(b_range.1.cmp(&a_range.1))
+        });
+        spans.into_iter().map(|(_, span)| span).collect()
     }
 }
@@ -0,0 +1,147 @@
+use anyhow::{bail, Context, Result};
+use libloading::{Library, Symbol};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tree_sitter::Language;
+
+use super::SyntaxEngine;
+
+/// Parses one `<extension> <symbol-name> <library-file>` line from a grammar
+/// config, returning `None` for blank lines and `#` comments. Split out from
+/// `load_from_config` so the parsing logic can be tested without real grammar
+/// shared libraries on disk.
+fn parse_config_line(line: &str) -> Result<Option<(&str, &str, &str)>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(ext), Some(sym), Some(lib)) => Ok(Some((ext, sym, lib))),
+        _ => bail!("Malformed grammar config line: {line}"),
+    }
+}
+
+/// A tree-sitter grammar loaded at runtime from a shared library rather than
+/// linked in at compile time.
+///
+/// The `Library` handle is kept alive for as long as this struct lives, since
+/// `language` is a thin wrapper around a function pointer that points into the
+/// library's mapped memory — dropping the library before the language would
+/// leave it dangling.
+pub struct LoadedGrammar {
+    pub name: String,
+    pub language: Language,
+    _library: Library,
+}
+
+/// Maps file extensions to runtime-loaded tree-sitter grammars, so a new
+/// language can be added by dropping a `.so`/`.dll`/`.dylib` into a `grammars/`
+/// directory instead of recompiling the IDE. The editor's callers check the
+/// compile-time `SupportedLanguage` enum first and consult this registry only for
+/// extensions the enum doesn't know, so a registry entry does not override a
+/// built-in language.
+#[derive(Default)]
+pub struct GrammarRegistry {
+    by_extension: HashMap<String, LoadedGrammar>,
+}
+
+impl GrammarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every grammar listed in `config_path`, a text file with one
+    /// `<extension> <symbol-name> <library-file>` triple per line (blank lines
+    /// and `#` comments are skipped). Library paths are resolved relative to
+    /// `grammars_dir`.
+    pub fn load_from_config(grammars_dir: &Path, config_path: &Path) -> Result<Self> {
+        let config = fs::read_to_string(config_path).with_context(|| {
+            format!("Failed to read grammar config: {}", config_path.display())
+        })?;
+
+        let mut registry = Self::new();
+        for line in config.lines() {
+            let Some((extension, symbol, library_file)) = parse_config_line(line)? else {
+                continue;
+            };
+            registry.load_grammar(extension, symbol, &grammars_dir.join(library_file))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Loads a single grammar shared library and registers it for `extension`,
+    /// resolving the conventional `tree_sitter_<name>` symbol.
+    pub fn load_grammar(&mut self, extension: &str, symbol: &str, library_path: &Path) -> Result<()> {
+        // Safety: we trust `library_path` to point at a well-formed tree-sitter
+        // grammar shared library exposing the given `tree_sitter_<name>` symbol,
+        // as documented on `GrammarRegistry`.
+        unsafe {
+            let library = Library::new(library_path).with_context(|| {
+                format!("Failed to load grammar library: {}", library_path.display())
+            })?;
+            let constructor: Symbol<unsafe extern "C" fn() -> Language> =
+                library.get(symbol.as_bytes()).with_context(|| {
+                    format!(
+                        "Grammar library {} has no symbol `{symbol}`",
+                        library_path.display()
+                    )
+                })?;
+            let language = constructor();
+
+            self.by_extension.insert(
+                extension.to_string(),
+                LoadedGrammar {
+                    name: symbol.trim_start_matches("tree_sitter_").to_string(),
+                    language,
+                    _library: library,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    pub fn grammar_for_extension(&self, extension: &str) -> Option<&LoadedGrammar> {
+        self.by_extension.get(extension)
+    }
+
+    /// Convenience constructor for a [`SyntaxEngine`] backed by a registry-loaded
+    /// grammar, mirroring `SyntaxEngine::new` for the built-in enum.
+    pub fn create_engine(&self, extension: &str) -> Option<SyntaxEngine> {
+        let grammar = self.grammar_for_extension(extension)?;
+        Some(SyntaxEngine::from_loaded(grammar.name.clone(), grammar.language))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_line_well_formed() {
+        let parsed = parse_config_line("zig tree_sitter_zig libtree-sitter-zig.so").unwrap();
+        assert_eq!(parsed, Some(("zig", "tree_sitter_zig", "libtree-sitter-zig.so")));
+    }
+
+    #[test]
+    fn test_parse_config_line_skips_blank_and_comments() {
+        assert_eq!(parse_config_line("").unwrap(), None);
+        assert_eq!(parse_config_line("   ").unwrap(), None);
+        assert_eq!(parse_config_line("# a comment").unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_config_line_tolerates_extra_whitespace() {
+        let parsed = parse_config_line("  zig   tree_sitter_zig   libtree-sitter-zig.so  ").unwrap();
+        assert_eq!(parsed, Some(("zig", "tree_sitter_zig", "libtree-sitter-zig.so")));
+    }
+
+    #[test]
+    fn test_parse_config_line_rejects_malformed() {
+        assert!(parse_config_line("zig tree_sitter_zig").is_err());
+        assert!(parse_config_line("zig").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use serde::Serialize;
+use tree_sitter::{Query, QueryCursor, Tree};
+
+use super::{SerializableRange, SupportedLanguage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Struct,
+    Enum,
+    Interface,
+    Impl,
+    Trait,
+    Module,
+}
+
+impl SymbolKind {
+    fn from_capture_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "function" => Some(Self::Function),
+            "method" => Some(Self::Method),
+            "class" => Some(Self::Class),
+            "struct" => Some(Self::Struct),
+            "enum" => Some(Self::Enum),
+            "interface" => Some(Self::Interface),
+            "impl" => Some(Self::Impl),
+            "trait" => Some(Self::Trait),
+            "module" => Some(Self::Module),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: SerializableRange,
+    pub name_range: SerializableRange,
+    pub children: Vec<OutlineItem>,
+}
+
+struct RawItem {
+    name: String,
+    kind: SymbolKind,
+    range: SerializableRange,
+    name_range: SerializableRange,
+    start_byte: usize,
+    end_byte: usize,
+}
+
+/// Per-language outline queries. Each pattern captures the declaration node as
+/// `@item.<kind>` and its identifier as `@name` — the same `@name`/`@item` shape
+/// rust-analyzer and Zed use for their outline/breadcrumb panels.
+fn outline_query(language: &SupportedLanguage) -> &'static str {
+    match language {
+        SupportedLanguage::Rust => {
+            r#"
+            (function_item name: (identifier) @name) @item.function
+            (struct_item name: (type_identifier) @name) @item.struct
+            (enum_item name: (type_identifier) @name) @item.enum
+            (trait_item name: (type_identifier) @name) @item.trait
+            (impl_item type: (type_identifier) @name) @item.impl
+            (mod_item name: (identifier) @name) @item.module
+            "#
+        }
+        SupportedLanguage::Python => {
+            r#"
+            (function_definition name: (identifier) @name) @item.function
+            (class_definition name: (identifier) @name) @item.class
+            "#
+        }
+        SupportedLanguage::JavaScript => {
+            r#"
+            (function_declaration name: (identifier) @name) @item.function
+            (method_definition name: (property_identifier) @name) @item.method
+            (class_declaration name: (identifier) @name) @item.class
+            "#
+        }
+        SupportedLanguage::TypeScript | SupportedLanguage::TSX => {
+            r#"
+            (function_declaration name: (identifier) @name) @item.function
+            (method_definition name: (property_identifier) @name) @item.method
+            (class_declaration name: (type_identifier) @name) @item.class
+            (interface_declaration name: (type_identifier) @name) @item.interface
+            "#
+        }
+        SupportedLanguage::CPP => {
+            r#"
+            (function_definition declarator: (function_declarator declarator: (identifier) @name)) @item.function
+            (class_specifier name: (type_identifier) @name) @item.class
+            (struct_specifier name: (type_identifier) @name) @item.struct
+            (enum_specifier name: (type_identifier) @name) @item.enum
+            "#
+        }
+        SupportedLanguage::CSharp => {
+            r#"
+            (method_declaration name: (identifier) @name) @item.method
+            (class_declaration name: (identifier) @name) @item.class
+            (struct_declaration name: (identifier) @name) @item.struct
+            (enum_declaration name: (identifier) @name) @item.enum
+            (interface_declaration name: (identifier) @name) @item.interface
+            "#
+        }
+    }
+}
+
+/// Extracts a nested symbol tree (functions, methods, classes/structs/enums, impl
+/// blocks, ...) from a parsed file, suitable for a symbols/breadcrumb panel.
+pub fn extract_outline(tree: &Tree, source: &str, language: &SupportedLanguage) -> Vec<OutlineItem> {
+    let query = match Query::new(language.tree_sitter_language(), outline_query(language)) {
+        Ok(q) => q,
+        Err(_) => return Vec::new(),
+    };
+    let capture_names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+
+    let mut items = Vec::new();
+    for m in cursor.matches(&query, tree.root_node(), source.as_bytes()) {
+        let mut name_node = None;
+        let mut item_node = None;
+        let mut kind = None;
+
+        for capture in m.captures {
+            let capture_name = &capture_names[capture.index as usize];
+            if capture_name == "name" {
+                name_node = Some(capture.node);
+            } else if let Some(suffix) = capture_name.strip_prefix("item.") {
+                item_node = Some(capture.node);
+                kind = SymbolKind::from_capture_suffix(suffix);
+            }
+        }
+
+        if let (Some(name_node), Some(item_node), Some(kind)) = (name_node, item_node, kind) {
+            let name = name_node
+                .utf8_text(source.as_bytes())
+                .unwrap_or_default()
+                .to_string();
+            items.push(RawItem {
+                name,
+                kind,
+                range: item_node.range().into(),
+                name_range: name_node.range().into(),
+                start_byte: item_node.start_byte(),
+                end_byte: item_node.end_byte(),
+            });
+        }
+    }
+
+    items.sort_by_key(|item| item.start_byte);
+    nest(&items)
+}
+
+/// Reconstructs parent/child nesting from node containment: `items` must be sorted
+/// by `start_byte` ascending and, since they come from real AST node ranges, never
+/// partially overlap (each pair is either disjoint or fully nested).
+fn nest(items: &[RawItem]) -> Vec<OutlineItem> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < items.len() {
+        let parent = &items[i];
+        let mut j = i + 1;
+        while j < items.len() && items[j].start_byte < parent.end_byte {
+            j += 1;
+        }
+        result.push(OutlineItem {
+            name: parent.name.clone(),
+            kind: parent.kind,
+            range: parent.range.clone(),
+            name_range: parent.name_range.clone(),
+            children: nest(&items[i + 1..j]),
+        });
+        i = j;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::SyntaxEngine;
+
+    fn raw_item(name: &str, kind: SymbolKind, start_byte: usize, end_byte: usize) -> RawItem {
+        let range = SerializableRange {
+            start_row: 0,
+            start_col: start_byte,
+            end_row: 0,
+            end_col: end_byte,
+        };
+        RawItem {
+            name: name.to_string(),
+            kind,
+            range: range.clone(),
+            name_range: range,
+            start_byte,
+            end_byte,
+        }
+    }
+
+    #[test]
+    fn test_nest_flat_siblings() {
+        let items = vec![
+            raw_item("a", SymbolKind::Function, 0, 5),
+            raw_item("b", SymbolKind::Function, 5, 10),
+        ];
+        let nested = nest(&items);
+        assert_eq!(nested.len(), 2);
+        assert!(nested[0].children.is_empty());
+        assert!(nested[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_nest_parent_with_child() {
+        let items = vec![
+            raw_item("Outer", SymbolKind::Impl, 0, 20),
+            raw_item("inner", SymbolKind::Method, 2, 10),
+        ];
+        let nested = nest(&items);
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].name, "Outer");
+        assert_eq!(nested[0].children.len(), 1);
+        assert_eq!(nested[0].children[0].name, "inner");
+    }
+
+    #[test]
+    fn test_nest_grandchildren() {
+        let items = vec![
+            raw_item("Outer", SymbolKind::Module, 0, 30),
+            raw_item("Middle", SymbolKind::Impl, 2, 20),
+            raw_item("inner", SymbolKind::Method, 4, 10),
+        ];
+        let nested = nest(&items);
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].children.len(), 1);
+        assert_eq!(nested[0].children[0].children.len(), 1);
+        assert_eq!(nested[0].children[0].children[0].name, "inner");
+    }
+
+    #[test]
+    fn test_extract_outline_rust_function_and_struct() {
+        let mut engine = SyntaxEngine::new(SupportedLanguage::Rust);
+        let source = "struct Foo;\n\nfn bar() {}\n";
+        let tree = engine.parse(source).expect("parse failed");
+        let items = extract_outline(&tree, source, &SupportedLanguage::Rust);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].name, "Foo");
+        assert_eq!(items[0].kind, SymbolKind::Struct);
+        assert_eq!(items[1].name, "bar");
+        assert_eq!(items[1].kind, SymbolKind::Function);
+    }
+}
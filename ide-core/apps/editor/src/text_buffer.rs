@@ -3,6 +3,7 @@ use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
+use tree_sitter::{InputEdit, Point};
 use crate::syntax::SupportedLanguage;
 use crate::syntax::{SyntaxEngine, HighlightSpan};
 
@@ -15,6 +16,7 @@ pub struct BufferMetadata {
 pub struct TextBuffer {
     rope: Rope,
     metadata: BufferMetadata,
+    syntax: Option<SyntaxEngine>,
 }
 
 #[allow(dead_code)]
@@ -42,6 +44,7 @@ impl TextBuffer {
                 path: Some(path_ref.to_path_buf()),
                 trailing_newline,
             },
+            syntax: None,
         })
     }
 
@@ -52,12 +55,21 @@ impl TextBuffer {
                 path: None,
                 trailing_newline: false,
             },
+            syntax: None,
         }
     }
 
     pub fn normalize_newlines(&mut self) {
         let text = self.rope.to_string().replace("\r\n", "\n");
         self.rope = Rope::from_str(&text);
+        // Every `\r\n` removed shifts every byte offset after it, so this can't be
+        // expressed as the single contiguous InputEdit the other mutators compute.
+        // Drop the stored tree instead of feeding it a bogus edit; the next
+        // `parse_syntax` will do a full reparse rather than reusing a tree whose
+        // offsets no longer match the normalized source.
+        if let Some(engine) = self.syntax.as_mut() {
+            engine.reset();
+        }
     }
 
     pub fn line_count(&self) -> usize {
@@ -82,8 +94,10 @@ impl TextBuffer {
         }
         let start = self.rope.line_to_char(index);
         let end = self.rope.line_to_char(index + 1);
+        let edit = self.input_edit(start, end, text);
         self.rope.remove(start..end);
         self.rope.insert(start, text);
+        self.apply_edit(edit);
         Ok(())
     }
 
@@ -132,7 +146,6 @@ impl TextBuffer {
             anyhow::bail!("Line index out of bounds");
         }
 
-    
         let char_idx = self.rope.line_to_char(index);
         let line = if text.ends_with('\n') {
             text.to_string()
@@ -140,7 +153,9 @@ impl TextBuffer {
             format!("{text}\n")
         };
 
+        let edit = self.input_edit(char_idx, char_idx, &line);
         self.rope.insert(char_idx, &line);
+        self.apply_edit(edit);
         Ok(())
     }
 
@@ -151,7 +166,10 @@ impl TextBuffer {
             format!("{text}\n")
         };
 
+        let char_idx = self.rope.len_chars();
+        let edit = self.input_edit(char_idx, char_idx, &line);
         self.rope.append(Rope::from_str(&line));
+        self.apply_edit(edit);
         Ok(())
     }
 
@@ -162,20 +180,76 @@ impl TextBuffer {
 
         let start = self.rope.line_to_char(index);
         let end = self.rope.line_to_char(index + 1);
+        let edit = self.input_edit(start, end, "");
         self.rope.remove(start..end);
+        self.apply_edit(edit);
         Ok(())
     }
 
-    pub fn parse_syntax(&self, language: SupportedLanguage) -> Option<tree_sitter::Tree> {
-        let mut engine = SyntaxEngine::new(language);
+    /// Attaches a syntax engine for `language` to this buffer, discarding any tree from
+    /// a previous language. Subsequent mutations are fed to it as incremental edits.
+    pub fn set_language(&mut self, language: SupportedLanguage) {
+        self.syntax = Some(SyntaxEngine::new(language));
+    }
+
+    pub fn parse_syntax(&mut self) -> Option<tree_sitter::Tree> {
         let text = self.rope.to_string();
-        engine.parse(&text)
+        self.syntax.as_mut()?.parse(&text).ok()
     }
 
-    pub fn extract_highlights(&self, language: SupportedLanguage) -> Vec<HighlightSpan> {
-        let mut engine = SyntaxEngine::new(language);
+    pub fn extract_highlights(&mut self) -> Vec<HighlightSpan> {
         let text = self.rope.to_string();
-        engine.extract_highlights(&text)
+        match self.syntax.as_mut() {
+            Some(engine) => engine.extract_highlights(&text),
+            None => vec![],
+        }
+    }
+
+    /// Computes the tree-sitter `InputEdit` for replacing `start_char..old_end_char`
+    /// (in rope char indices, measured before the mutation) with `new_text`. Must be
+    /// called before the rope itself is mutated, since it reads byte/line positions
+    /// off the pre-edit rope.
+    fn input_edit(&self, start_char: usize, old_end_char: usize, new_text: &str) -> InputEdit {
+        let start_byte = self.rope.char_to_byte(start_char);
+        let old_end_byte = self.rope.char_to_byte(old_end_char);
+        let new_end_byte = start_byte + new_text.len();
+
+        let start_position = self.point_at_char(start_char);
+        let old_end_position = self.point_at_char(old_end_char);
+        let new_end_position = Self::advance_point(start_position, new_text);
+
+        InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position,
+        }
+    }
+
+    /// Feeds `edit` to the attached syntax engine's stored tree, if any.
+    fn apply_edit(&mut self, edit: InputEdit) {
+        if let Some(engine) = self.syntax.as_mut() {
+            engine.edit(&edit);
+        }
+    }
+
+    fn point_at_char(&self, char_idx: usize) -> Point {
+        let row = self.rope.char_to_line(char_idx);
+        let row_start_byte = self.rope.char_to_byte(self.rope.line_to_char(row));
+        let column = self.rope.char_to_byte(char_idx) - row_start_byte;
+        Point::new(row, column)
+    }
+
+    fn advance_point(start: Point, text: &str) -> Point {
+        match text.rfind('\n') {
+            Some(last_newline) => Point::new(
+                start.row + text.matches('\n').count(),
+                text.len() - last_newline - 1,
+            ),
+            None => Point::new(start.row, start.column + text.len()),
+        }
     }
 }
 
@@ -251,10 +325,11 @@ mod tests {
     #[test]
     fn test_syntax_parse_python() {
         let mut buf = TextBuffer::empty();
+        buf.set_language(SupportedLanguage::Python);
         buf.set_line(0, "def foo():\n").unwrap();
         buf.append_line("    return 42").unwrap();
 
-        let tree = buf.parse_syntax(SupportedLanguage::Python).expect("Failed to parse");
+        let tree = buf.parse_syntax().expect("Failed to parse");
         let root = tree.root_node();
 
         assert_eq!(root.kind(), "module");
@@ -267,7 +342,7 @@ mod tests {
         let mut engine = SyntaxEngine::new(SupportedLanguage::Rust);
         let source = r#"fn main() { println!("Hello"); }"#;
         let tree = engine.parse(source);
-        assert!(tree.is_some());
+        assert!(tree.is_ok());
     }
 
     #[test]
@@ -275,17 +350,35 @@ mod tests {
         let mut engine = SyntaxEngine::new(SupportedLanguage::TypeScript);
         let source = r#"function greet(name: string): void { console.log(name); }"#;
         let tree = engine.parse(source);
-        assert!(tree.is_some());
+        assert!(tree.is_ok());
     }
 
     #[test]
     fn test_extract_highlights_python() {
         let mut buf = TextBuffer::empty();
+        buf.set_language(SupportedLanguage::Python);
+        buf.set_line(0, "def foo():\n").unwrap();
+        buf.append_line("    return 42").unwrap();
+
+        let highlights = buf.extract_highlights();
+        assert!(highlights.iter().any(|h| h.highlight_type == "keyword"));
+    }
+
+    #[test]
+    fn test_incremental_reparse_reuses_tree() {
+        let mut buf = TextBuffer::empty();
+        buf.set_language(SupportedLanguage::Python);
         buf.set_line(0, "def foo():\n").unwrap();
         buf.append_line("    return 42").unwrap();
+        buf.parse_syntax().expect("initial parse failed");
 
-        let highlights = buf.extract_highlights(SupportedLanguage::Python);
-        assert!(highlights.iter().any(|h| h.highlight_type == "function_definition"));
+        // Editing a single line should still reparse successfully using the edited tree.
+        buf.set_line(1, "    return 43\n").unwrap();
+        let tree = buf.parse_syntax().expect("incremental parse failed");
+        let root = tree.root_node();
+
+        assert_eq!(root.kind(), "module");
+        assert!(!root.has_error());
     }
 }
 
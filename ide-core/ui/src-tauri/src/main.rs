@@ -1,10 +1,87 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
-use tauri::command;
+use std::sync::Mutex;
+use tauri::{command, State};
+use editor::diff::compute_edit;
 use editor::syntax::{HighlightSpan, SupportedLanguage, SyntaxEngine};
+use editor::syntax::grammar_registry::GrammarRegistry;
+use editor::syntax::outline::OutlineItem;
+use editor::file_tree::{self, FileNode};
+use editor::stats::{self, LanguageStats};
+
+/// One open buffer's syntax engine plus the text it was last parsed from, so the
+/// next call can diff against it instead of starting from an empty tree.
+struct BufferEngine {
+    language: String,
+    source: String,
+    engine: SyntaxEngine,
+}
+
+/// Buffers are keyed by `path`, the same identifier the frontend uses to tell
+/// the backend which file a `contents` string belongs to.
+///
+/// `grammars` starts empty; the frontend populates it with a `load_grammars`
+/// call at startup so extensions outside the built-in `SupportedLanguage` enum
+/// still get highlighting/outline support. `retired_grammars` holds every
+/// registry `load_grammars` has since superseded: a `BufferEngine` built from
+/// `grammars.create_engine(...)` holds a `tree_sitter::Language` pointing into
+/// that registry's `Library`, so dropping a superseded registry outright would
+/// unload the `.so` out from under any buffer still using it. Keeping it
+/// around for the rest of the process's life keeps those pointers valid.
+#[derive(Default)]
+struct EditorState {
+    buffers: Mutex<HashMap<String, BufferEngine>>,
+    grammars: Mutex<GrammarRegistry>,
+    retired_grammars: Mutex<Vec<GrammarRegistry>>,
+}
+
+/// Looks up (or creates) the `SyntaxEngine` for `path`, feeding it an
+/// `InputEdit` computed against the previous call's text when the language
+/// hasn't changed, so `f` sees an incrementally-reparsed tree instead of a
+/// fresh one on every keystroke. Falls back to `grammars` for extensions the
+/// built-in `SupportedLanguage` enum doesn't know.
+fn with_buffer_engine<R>(
+    buffers: &mut HashMap<String, BufferEngine>,
+    grammars: &GrammarRegistry,
+    path: &str,
+    language: &str,
+    contents: &str,
+    f: impl FnOnce(&mut SyntaxEngine, &str) -> R,
+) -> Result<R, String> {
+    let needs_fresh = buffers
+        .get(path)
+        .map_or(true, |existing| existing.language != language);
+
+    if needs_fresh {
+        let engine = match SupportedLanguage::from_extension(language) {
+            Some(lang) => SyntaxEngine::new(lang),
+            None => grammars
+                .create_engine(language)
+                .ok_or_else(|| format!("Unsupported language: {}", language))?,
+        };
+        buffers.insert(
+            path.to_string(),
+            BufferEngine {
+                language: language.to_string(),
+                source: contents.to_string(),
+                engine,
+            },
+        );
+    } else if let Some(existing) = buffers.get_mut(path) {
+        let edit = compute_edit(&existing.source, contents);
+        existing.engine.edit(&edit);
+        existing.source = contents.to_string();
+    }
+
+    let entry = buffers
+        .get_mut(path)
+        .expect("buffer entry was just inserted or updated above");
+    Ok(f(&mut entry.engine, contents))
+}
 
 #[command]
 fn save_buffer(contents: String) -> Result<(), String> {
@@ -19,32 +96,79 @@ fn load_buffer() -> Result<String, String> {
 }
 
 #[command]
-fn get_highlights(contents: String, language: String) -> Result<Vec<HighlightSpan>, String> {
-    let lang = SupportedLanguage::from_extension(&language)
-        .ok_or_else(|| format!("Unsupported language: {}", language))?;
+fn get_highlights(
+    state: State<EditorState>,
+    path: String,
+    contents: String,
+    language: String,
+) -> Result<Vec<HighlightSpan>, String> {
+    let mut buffers = state.buffers.lock().map_err(|e| e.to_string())?;
+    let grammars = state.grammars.lock().map_err(|e| e.to_string())?;
+    with_buffer_engine(&mut buffers, &grammars, &path, &language, &contents, |engine, contents| {
+        engine.extract_highlights(contents)
+    })
+}
 
-    let mut engine = SyntaxEngine::new(lang);
-    Ok(engine.extract_highlights(&contents))
+#[command]
+fn get_outline(
+    state: State<EditorState>,
+    path: String,
+    contents: String,
+    language: String,
+) -> Result<Vec<OutlineItem>, String> {
+    let mut buffers = state.buffers.lock().map_err(|e| e.to_string())?;
+    let grammars = state.grammars.lock().map_err(|e| e.to_string())?;
+    with_buffer_engine(&mut buffers, &grammars, &path, &language, &contents, |engine, contents| {
+        engine.extract_outline(contents)
+    })
 }
 
+/// Loads every grammar listed in `config_path` (library paths resolved
+/// relative to `grammars_dir`) into the app's shared registry, so extensions
+/// outside the built-in enum get highlighting/outline/file-tree support. Can
+/// be called again later (e.g. after dropping a new grammar into the
+/// directory) — the registry it replaces is kept in `retired_grammars`
+/// rather than dropped, since buffers opened under it may still be using
+/// engines resolved through it.
 #[command]
-fn list_files(root: String) -> Result<Vec<String>, String> {
-    let paths = std::fs::read_dir(root)
+fn load_grammars(state: State<EditorState>, grammars_dir: String, config_path: String) -> Result<(), String> {
+    let registry = GrammarRegistry::load_from_config(
+        std::path::Path::new(&grammars_dir),
+        std::path::Path::new(&config_path),
+    )
+    .map_err(|e| e.to_string())?;
+    let mut grammars = state.grammars.lock().map_err(|e| e.to_string())?;
+    let previous = std::mem::replace(&mut *grammars, registry);
+    state
+        .retired_grammars
+        .lock()
         .map_err(|e| e.to_string())?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().is_file())
-        .map(|entry| entry.path().display().to_string())
-        .collect();
-    Ok(paths)
+        .push(previous);
+    Ok(())
+}
+
+#[command]
+fn list_files(state: State<EditorState>, root: String) -> Result<FileNode, String> {
+    let grammars = state.grammars.lock().map_err(|e| e.to_string())?;
+    file_tree::walk_project(std::path::Path::new(&root), Some(&grammars)).map_err(|e| e.to_string())
+}
+
+#[command]
+fn get_stats(root: String) -> Result<HashMap<String, LanguageStats>, String> {
+    stats::collect_stats(std::path::Path::new(&root)).map_err(|e| e.to_string())
 }
 
 fn main() {
     tauri::Builder::default()
+        .manage(EditorState::default())
         .invoke_handler(tauri::generate_handler![
             save_buffer,
             load_buffer,
             get_highlights,
-            list_files
+            get_outline,
+            load_grammars,
+            list_files,
+            get_stats
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");